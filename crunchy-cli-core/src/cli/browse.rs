@@ -0,0 +1,121 @@
+use crate::cli::output::{
+    convert_to_formatted_outputs, media_collection_to_output, print_outputs, OutputFields,
+    OutputFormat,
+};
+use crate::utils::context::Context;
+use crate::Execute;
+use crunchyroll_rs::search::{BrowseOptions, BrowseSortType};
+
+#[derive(Clone, Debug)]
+pub enum BrowseSort {
+    Popularity,
+    NewlyAdded,
+    Alphabetical,
+}
+
+impl BrowseSort {
+    fn parse(s: &str) -> Result<BrowseSort, String> {
+        Ok(match s.to_lowercase().as_str() {
+            "popularity" => BrowseSort::Popularity,
+            "newly-added" | "newly_added" => BrowseSort::NewlyAdded,
+            "alphabetical" => BrowseSort::Alphabetical,
+            _ => return Err(format!("'{}' is not a valid sort type", s)),
+        })
+    }
+}
+
+#[derive(Debug, clap::Parser)]
+#[clap(about = "Browse the catalog by sort order and category instead of a keyword")]
+#[command(arg_required_else_help(true))]
+pub struct Browse {
+    #[arg(help = "Number of results to fetch")]
+    #[arg(short = 'n', long, default_value_t = 10)]
+    limit: u32,
+    #[arg(help = "How to sort the catalog. \
+    Available options are: 'popularity', 'newly-added' and 'alphabetical'")]
+    #[arg(long, default_value = "popularity")]
+    #[arg(value_parser = BrowseSort::parse)]
+    sort: BrowseSort,
+    #[arg(help = "Only return series/movies matching one of the given categories")]
+    #[arg(long = "category")]
+    categories: Vec<String>,
+    #[arg(help = "Only return series/movies which are dubbed")]
+    #[arg(long, default_value_t = false)]
+    dubbed: bool,
+    #[arg(help = "Only return series/movies part of the given simulcast season")]
+    #[arg(long)]
+    simulcast_season: Option<String>,
+
+    #[arg(long, default_value_t = false)]
+    id: bool,
+    #[arg(long, default_value_t = false)]
+    url: bool,
+    #[arg(long = "type", default_value_t = false)]
+    type_: bool,
+    #[arg(long, default_value_t = false)]
+    title: bool,
+    #[arg(long, default_value_t = false)]
+    description: bool,
+
+    #[arg(help = "Format in which the output should be displayed. \
+    Available options are: 'csv', 'json' and 'table'")]
+    #[arg(long_help = "Format in which the output should be displayed. \
+    Available options are: 'csv', 'quoted-csv', 'json', 'table' and (when built with the \
+    'report-yaml' feature) 'yaml'. Note that 'quoted-csv' will remove all newlines to keep \
+    the output parsable")]
+    #[arg(long, default_value = "csv")]
+    #[arg(value_parser = OutputFormat::parse)]
+    output_format: OutputFormat,
+}
+
+impl Browse {
+    fn output_fields(&self) -> OutputFields {
+        OutputFields {
+            id: self.id,
+            url: self.url,
+            type_: self.type_,
+            title: self.title,
+            description: self.description,
+            score: false,
+            rank: false,
+            popularity_score: false,
+            release_date: false,
+            audio_locales: false,
+            subtitle_locales: false,
+        }
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl Execute for Browse {
+    async fn execute(self, ctx: Context) -> anyhow::Result<()> {
+        let mut browse_options = BrowseOptions::default().limit(self.limit);
+        browse_options = match self.sort {
+            BrowseSort::Popularity => browse_options.sort(BrowseSortType::Popularity),
+            BrowseSort::NewlyAdded => browse_options.sort(BrowseSortType::NewlyAdded),
+            BrowseSort::Alphabetical => browse_options.sort(BrowseSortType::Alphabetical),
+        };
+        if !self.categories.is_empty() {
+            browse_options = browse_options.categories(self.categories);
+        }
+        if self.dubbed {
+            browse_options = browse_options.is_dubbed(true);
+        }
+        if let Some(simulcast_season) = self.simulcast_season {
+            browse_options = browse_options.simulcast_season(simulcast_season);
+        }
+
+        let browse = ctx.crunchy.browse(browse_options).await?;
+
+        let mut outputs = vec![];
+
+        for result in browse.items {
+            if let Some(output) = media_collection_to_output(result).await? {
+                outputs.push(output)
+            }
+        }
+
+        let formatted_outputs = convert_to_formatted_outputs(self.output_fields(), outputs);
+        print_outputs(formatted_outputs, &self.output_format)
+    }
+}
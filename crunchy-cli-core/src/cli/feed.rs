@@ -0,0 +1,202 @@
+use crate::cli::output::media_collection_to_output;
+use crate::utils::context::Context;
+use crate::utils::parse::parse_url;
+use crate::Execute;
+use anyhow::bail;
+use crunchyroll_rs::MediaCollection;
+use quick_xml::escape::escape;
+use quick_xml::events::{BytesEnd, BytesStart, BytesText, Event};
+use quick_xml::Writer;
+use std::io::Cursor;
+
+#[derive(Clone, Debug)]
+pub enum FeedFormat {
+    Rss,
+    Opml,
+}
+
+impl FeedFormat {
+    fn parse(s: &str) -> Result<FeedFormat, String> {
+        Ok(match s.to_lowercase().as_str() {
+            "rss" => FeedFormat::Rss,
+            "opml" => FeedFormat::Opml,
+            _ => return Err(format!("'{}' is not a valid feed format", s)),
+        })
+    }
+}
+
+#[derive(Debug, clap::Parser)]
+#[clap(about = "Export a series as an RSS feed, or a set of series as an OPML outline")]
+#[command(arg_required_else_help(true))]
+pub struct Feed {
+    #[arg(help = "Number of episodes to include in an RSS feed")]
+    #[arg(short = 'n', long, default_value_t = 20)]
+    limit: u32,
+    #[arg(help = "Format of the generated feed. \
+    Available options are: 'rss' and 'opml'")]
+    #[arg(long, default_value = "rss")]
+    #[arg(value_parser = FeedFormat::parse)]
+    format: FeedFormat,
+
+    #[arg(help = "One or more series urls/ids. 'rss' only supports a single series, \
+    'opml' supports any number")]
+    inputs: Vec<String>,
+}
+
+#[async_trait::async_trait(?Send)]
+impl Execute for Feed {
+    async fn execute(self, ctx: Context) -> anyhow::Result<()> {
+        match self.format {
+            FeedFormat::Rss => {
+                let input = match self.inputs.as_slice() {
+                    [input] => input,
+                    [] => bail!("'rss' feeds require a series url/id"),
+                    _ => bail!("'rss' feeds only support a single series"),
+                };
+                println!("{}", series_rss(&ctx, input, self.limit).await?)
+            }
+            FeedFormat::Opml => println!("{}", series_opml(&ctx, &self.inputs).await?),
+        }
+
+        Ok(())
+    }
+}
+
+async fn resolve_series(ctx: &Context, input: &str) -> anyhow::Result<crunchyroll_rs::Series> {
+    let (media_collection, _) = parse_url(&ctx.crunchy, input.to_string(), true).await?;
+    match media_collection {
+        MediaCollection::Series(series) => Ok(series),
+        _ => bail!("'{}' does not point to a series", input),
+    }
+}
+
+async fn series_rss(ctx: &Context, input: &str, limit: u32) -> anyhow::Result<String> {
+    let series = resolve_series(ctx, input).await?;
+    let series_url = format!(
+        "https://www.crunchyroll.com/series/{}/{}",
+        series.id, series.slug_title
+    );
+
+    let mut episodes = vec![];
+    for season in series.seasons().await? {
+        for episode in season.episodes().await? {
+            episodes.push(episode)
+        }
+    }
+    // latest episodes first
+    episodes.sort_by(|a, b| b.episode_air_date.cmp(&a.episode_air_date));
+    episodes.truncate(limit as usize);
+
+    let mut writer = Writer::new_with_indent(Cursor::new(Vec::new()), b' ', 2);
+    writer.write_event(Event::Start(BytesStart::new("rss").with_attributes([(
+        "version", "2.0",
+    )])))?;
+    writer.write_event(Event::Start(BytesStart::new("channel")))?;
+    write_text_element(&mut writer, "title", &series.title)?;
+    write_text_element(&mut writer, "link", &series_url)?;
+    write_text_element(&mut writer, "description", &series.description)?;
+
+    for episode in episodes {
+        if let Some(output) = media_collection_to_output(MediaCollection::Episode(episode)).await?
+        {
+            writer.write_event(Event::Start(BytesStart::new("item")))?;
+            write_text_element(&mut writer, "title", &output.title)?;
+            write_text_element(&mut writer, "link", &output.url)?;
+            write_text_element(&mut writer, "guid", &output.id)?;
+            write_text_element(&mut writer, "description", &output.description)?;
+            if let Some(release_date) = &output.release_date {
+                write_text_element(&mut writer, "pubDate", release_date)?;
+            }
+            writer.write_event(Event::End(BytesEnd::new("item")))?;
+        }
+    }
+
+    writer.write_event(Event::End(BytesEnd::new("channel")))?;
+    writer.write_event(Event::End(BytesEnd::new("rss")))?;
+
+    Ok(String::from_utf8(writer.into_inner().into_inner())?)
+}
+
+async fn series_opml(ctx: &Context, inputs: &[String]) -> anyhow::Result<String> {
+    let mut writer = Writer::new_with_indent(Cursor::new(Vec::new()), b' ', 2);
+    writer.write_event(Event::Start(BytesStart::new("opml").with_attributes([(
+        "version", "2.0",
+    )])))?;
+    writer.write_event(Event::Start(BytesStart::new("head")))?;
+    write_text_element(&mut writer, "title", "crunchy-cli feed")?;
+    writer.write_event(Event::End(BytesEnd::new("head")))?;
+    writer.write_event(Event::Start(BytesStart::new("body")))?;
+
+    for input in inputs {
+        let series = resolve_series(ctx, input).await?;
+        let series_url = format!(
+            "https://www.crunchyroll.com/series/{}/{}",
+            series.id, series.slug_title
+        );
+        write_outline(&mut writer, &series.title, &series_url)?;
+    }
+
+    writer.write_event(Event::End(BytesEnd::new("body")))?;
+    writer.write_event(Event::End(BytesEnd::new("opml")))?;
+
+    Ok(String::from_utf8(writer.into_inner().into_inner())?)
+}
+
+/// Writes a single OPML `outline` element for a series. `with_attributes` writes values
+/// verbatim, unlike `BytesText::new` used for text nodes elsewhere in this file, so any `"`, `&`
+/// or `<` in `title`/`url` has to be escaped by hand to keep the XML well-formed.
+fn write_outline(
+    writer: &mut Writer<Cursor<Vec<u8>>>,
+    title: &str,
+    url: &str,
+) -> anyhow::Result<()> {
+    let escaped_title = escape(title);
+    let escaped_url = escape(url);
+    writer.write_event(Event::Empty(BytesStart::new("outline").with_attributes([
+        ("text", escaped_title.as_ref()),
+        ("type", "rss"),
+        ("htmlUrl", escaped_url.as_ref()),
+    ])))?;
+    Ok(())
+}
+
+fn write_text_element(
+    writer: &mut Writer<Cursor<Vec<u8>>>,
+    name: &str,
+    text: &str,
+) -> anyhow::Result<()> {
+    writer.write_event(Event::Start(BytesStart::new(name)))?;
+    writer.write_event(Event::Text(BytesText::new(text)))?;
+    writer.write_event(Event::End(BytesEnd::new(name)))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_outline_to_string(title: &str, url: &str) -> String {
+        let mut writer = Writer::new(Cursor::new(Vec::new()));
+        write_outline(&mut writer, title, url).unwrap();
+        String::from_utf8(writer.into_inner().into_inner()).unwrap()
+    }
+
+    #[test]
+    fn write_outline_escapes_ampersands_and_angle_brackets() {
+        let xml = write_outline_to_string("Fruits & <Vegetables>", "https://example.com/a");
+        assert!(!xml.contains("Fruits & <Vegetables>"));
+        assert!(xml.contains("Fruits &amp; &lt;Vegetables&gt;"));
+    }
+
+    #[test]
+    fn write_outline_escapes_quotes_in_attribute_values() {
+        let xml = write_outline_to_string(r#"A "Quoted" Title"#, "https://example.com/a");
+        assert!(!xml.contains(r#""A "Quoted" Title""#));
+    }
+
+    #[test]
+    fn write_outline_passes_through_plain_title() {
+        let xml = write_outline_to_string("Plain Title", "https://example.com/a");
+        assert!(xml.contains(r#"text="Plain Title""#));
+    }
+}
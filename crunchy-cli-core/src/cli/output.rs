@@ -0,0 +1,648 @@
+use crunchyroll_rs::search::SearchMetadata;
+use crunchyroll_rs::{Episode, MediaCollection, Series};
+use log::warn;
+use serde::Serialize;
+use serde_json::Value;
+
+#[derive(Clone, Debug)]
+pub enum OutputFormat {
+    Csv,
+    QuotedCsv,
+    Json,
+    #[cfg(feature = "report-yaml")]
+    Yaml,
+    Table,
+}
+
+impl OutputFormat {
+    pub fn parse(s: &str) -> Result<OutputFormat, String> {
+        Ok(match s.to_lowercase().as_str() {
+            "csv" => OutputFormat::Csv,
+            "quoted-csv" | "csv-quoted" => OutputFormat::QuotedCsv,
+            "json" => OutputFormat::Json,
+            #[cfg(feature = "report-yaml")]
+            "yaml" | "yml" => OutputFormat::Yaml,
+            "table" => OutputFormat::Table,
+            _ => return Err(format!("'{}' is not a valid output format", s)),
+        })
+    }
+}
+
+#[derive(Default)]
+pub struct Output {
+    pub id: String,
+    pub url: String,
+    pub type_: String,
+    pub title: String,
+    pub description: String,
+    /// Relevance score of the result. Always present for query/search results.
+    pub score: Option<f64>,
+    /// Position of the result in a keyword search. Only set for keyword searches.
+    pub rank: Option<u32>,
+    /// Popularity of the result. Only set for "similar" style lookups.
+    pub popularity_score: Option<f64>,
+    /// RFC 822 release date. Only set for episodes.
+    pub release_date: Option<String>,
+    /// Locales this result is dubbed in. Falls back to a guess from the slug title if the API
+    /// doesn't report any.
+    pub audio_locales: Vec<String>,
+    /// Locales this result has subtitles in.
+    pub subtitle_locales: Vec<String>,
+}
+
+#[serde_with::skip_serializing_none]
+#[derive(Serialize)]
+pub struct FormattedOutput {
+    pub id: Option<String>,
+    pub url: Option<String>,
+    #[serde(rename = "type")]
+    pub type_: Option<String>,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub score: Option<f64>,
+    pub rank: Option<u32>,
+    pub popularity_score: Option<f64>,
+    pub release_date: Option<String>,
+    pub audio_locales: Option<Vec<String>>,
+    pub subtitle_locales: Option<Vec<String>>,
+}
+
+/// Which fields of an [`Output`] should end up in the formatted result, as selected by the
+/// command's `--id`/`--url`/... flags.
+#[derive(Default, Clone, Copy)]
+pub struct OutputFields {
+    pub id: bool,
+    pub url: bool,
+    pub type_: bool,
+    pub title: bool,
+    pub description: bool,
+    pub score: bool,
+    pub rank: bool,
+    pub popularity_score: bool,
+    pub release_date: bool,
+    pub audio_locales: bool,
+    pub subtitle_locales: bool,
+}
+
+pub fn convert_to_formatted_outputs(
+    fields: OutputFields,
+    outputs: Vec<Output>,
+) -> Vec<FormattedOutput> {
+    let mut format_outputs = vec![];
+    for output in outputs {
+        format_outputs.push(FormattedOutput {
+            id: fields.id.then_some(output.id),
+            url: fields.url.then_some(output.url),
+            type_: fields.type_.then_some(output.type_),
+            title: fields.title.then_some(output.title),
+            description: fields.description.then_some(output.description),
+            score: fields.score.then_some(output.score).flatten(),
+            rank: fields.rank.then_some(output.rank).flatten(),
+            popularity_score: fields
+                .popularity_score
+                .then_some(output.popularity_score)
+                .flatten(),
+            release_date: fields.release_date.then_some(output.release_date).flatten(),
+            audio_locales: fields.audio_locales.then_some(output.audio_locales),
+            subtitle_locales: fields.subtitle_locales.then_some(output.subtitle_locales),
+        })
+    }
+    format_outputs
+}
+
+/// Keeps only outputs which have `locale` among their audio or subtitle locales.
+pub fn filter_by_locale(outputs: Vec<Output>, locale: &Option<String>) -> Vec<Output> {
+    let Some(locale) = locale else {
+        return outputs;
+    };
+    outputs
+        .into_iter()
+        .filter(|output| {
+            output.audio_locales.iter().any(|l| l == locale)
+                || output.subtitle_locales.iter().any(|l| l == locale)
+        })
+        .collect()
+}
+
+#[derive(Clone, Debug)]
+pub enum SortBy {
+    Score,
+    Rank,
+}
+
+impl SortBy {
+    pub fn parse(s: &str) -> Result<SortBy, String> {
+        Ok(match s.to_lowercase().as_str() {
+            "score" => SortBy::Score,
+            "rank" => SortBy::Rank,
+            _ => return Err(format!("'{}' is not a valid sort-by value", s)),
+        })
+    }
+}
+
+/// Reorders `outputs` best-matches-first according to `sort_by`, if given.
+pub fn sort_outputs(outputs: &mut [Output], sort_by: &Option<SortBy>) {
+    match sort_by {
+        Some(SortBy::Score) => outputs.sort_by(|a, b| {
+            b.score
+                .unwrap_or(0.0)
+                .partial_cmp(&a.score.unwrap_or(0.0))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        }),
+        Some(SortBy::Rank) => outputs.sort_by_key(|o| o.rank.unwrap_or(u32::MAX)),
+        None => {}
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Expand {
+    Seasons,
+    Episodes,
+}
+
+impl Expand {
+    pub fn parse(s: &str) -> Result<Expand, String> {
+        Ok(match s.to_lowercase().as_str() {
+            "season" | "seasons" => Expand::Seasons,
+            "episode" | "episodes" => Expand::Episodes,
+            _ => return Err(format!("'{}' is not a valid expand value", s)),
+        })
+    }
+}
+
+/// Expands a series into one [`Output`] row per season, or (for [`Expand::Episodes`]) per
+/// episode of every season, so a whole show can be dumped in one invocation instead of being
+/// collapsed into its single series row.
+pub async fn expand_series(series: Series, expand: &Expand) -> anyhow::Result<Vec<Output>> {
+    let mut outputs = vec![];
+
+    for season in series.seasons().await? {
+        match expand {
+            Expand::Seasons => outputs.push(Output {
+                id: season.id.clone(),
+                url: format!(
+                    "https://www.crunchyroll.com/series/{}/{}",
+                    series.id, series.slug_title
+                ),
+                type_: "season".to_string(),
+                title: season.title,
+                description: String::new(),
+                score: None,
+                rank: None,
+                popularity_score: None,
+                release_date: None,
+                audio_locales: audio_locales(
+                    season.audio_locales.iter().map(|l| l.to_string()).collect(),
+                    &series.slug_title,
+                ),
+                subtitle_locales: season.subtitle_locales.iter().map(|l| l.to_string()).collect(),
+            }),
+            Expand::Episodes => {
+                for episode in season.episodes().await? {
+                    outputs.push(episode_to_output(episode, (None, None, None)))
+                }
+            }
+        }
+    }
+
+    Ok(outputs)
+}
+
+/// Best-effort fallback for when the API doesn't directly report an audio locale: Crunchyroll
+/// dub slugs are suffixed with the dub's language, e.g. `attack-on-titan-german-dub`.
+fn locale_from_slug(slug_title: &str) -> Option<String> {
+    let slug = slug_title.strip_suffix("-dub").unwrap_or(slug_title);
+    Some(
+        match slug.rsplit('-').next()? {
+            "german" => "de-DE",
+            "castilian" => "es-ES",
+            "english" => "en-US",
+            _ => return None,
+        }
+        .to_string(),
+    )
+}
+
+/// Audio locales reported by the API, or (if empty) a best-effort guess from the slug title.
+fn audio_locales(reported: Vec<String>, slug_title: &str) -> Vec<String> {
+    if !reported.is_empty() {
+        return reported;
+    }
+    locale_from_slug(slug_title).into_iter().collect()
+}
+
+fn search_metadata(meta: &Option<SearchMetadata>) -> (Option<f64>, Option<u32>, Option<f64>) {
+    match meta {
+        Some(m) => (Some(m.score), m.rank, m.popularity_score),
+        None => (None, None, None),
+    }
+}
+
+/// Formats a timestamp as RFC 822, the date format RSS 2.0's `pubDate` requires.
+fn format_rfc822(date: &chrono::DateTime<chrono::Utc>) -> String {
+    date.format("%a, %d %b %Y %H:%M:%S %z").to_string()
+}
+
+/// Converts an episode into an [`Output`] row. `score_info` is `(score, rank, popularity_score)`,
+/// shared with [`search_metadata`] so callers that have no search metadata (e.g. [`expand_series`])
+/// can just pass `(None, None, None)`.
+fn episode_to_output(episode: Episode, score_info: (Option<f64>, Option<u32>, Option<f64>)) -> Output {
+    let (score, rank, popularity_score) = score_info;
+    Output {
+        id: episode.id.clone(),
+        url: format!(
+            "https://www.crunchyroll.com/watch/{}/{}",
+            episode.id, episode.slug_title
+        ),
+        type_: "episode".to_string(),
+        title: episode.title,
+        audio_locales: audio_locales(
+            episode.audio_locales.iter().map(|l| l.to_string()).collect(),
+            &episode.slug_title,
+        ),
+        subtitle_locales: episode
+            .subtitle_locales
+            .iter()
+            .map(|l| l.to_string())
+            .collect(),
+        description: episode.description,
+        score,
+        rank,
+        popularity_score,
+        release_date: Some(format_rfc822(&episode.episode_air_date)),
+    }
+}
+
+/// Converts a single query/search/similar result into an [`Output`] row, logging and returning
+/// `None` for collection kinds that don't map onto a single row (any season, or a movie listing
+/// with no movies attached).
+pub async fn media_collection_to_output(result: MediaCollection) -> anyhow::Result<Option<Output>> {
+    Ok(match result {
+        MediaCollection::Series(series) => {
+            let (score, rank, popularity_score) = search_metadata(&series.search_metadata);
+            Some(Output {
+                id: series.id.clone(),
+                url: format!(
+                    "https://www.crunchyroll.com/series/{}/{}",
+                    series.id, series.slug_title
+                ),
+                type_: "series".to_string(),
+                title: series.title,
+                audio_locales: audio_locales(
+                    series.audio_locales.iter().map(|l| l.to_string()).collect(),
+                    &series.slug_title,
+                ),
+                subtitle_locales: series
+                    .subtitle_locales
+                    .iter()
+                    .map(|l| l.to_string())
+                    .collect(),
+                description: series.description,
+                score,
+                rank,
+                popularity_score,
+                release_date: None,
+            })
+        }
+        MediaCollection::Season(_) => {
+            warn!("Found season, skipping");
+            None
+        }
+        MediaCollection::Episode(episode) => {
+            let score_info = search_metadata(&episode.search_metadata);
+            Some(episode_to_output(episode, score_info))
+        }
+        MediaCollection::MovieListing(movie_listing) => {
+            let (score, rank, popularity_score) = search_metadata(&movie_listing.search_metadata);
+            let movies = movie_listing.movies().await?;
+            if let Some(movie) = movies.into_iter().next() {
+                Some(Output {
+                    id: movie.id.clone(),
+                    url: format!(
+                        "https://www.crunchyroll.com/watch/{}/{}",
+                        movie.id, movie.slug_title
+                    ),
+                    type_: "movie".to_string(),
+                    title: movie.title,
+                    audio_locales: audio_locales(vec![], &movie.slug_title),
+                    subtitle_locales: vec![],
+                    description: movie.description,
+                    score,
+                    rank,
+                    popularity_score,
+                    release_date: None,
+                })
+            } else {
+                warn!("Movie listing queried but no movie found");
+                None
+            }
+        }
+        MediaCollection::Movie(movie) => {
+            let (score, rank, popularity_score) = search_metadata(&movie.search_metadata);
+            Some(Output {
+                id: movie.id.clone(),
+                url: format!(
+                    "https://www.crunchyroll.com/watch/{}/{}",
+                    movie.id, movie.slug_title
+                ),
+                type_: "movie".to_string(),
+                title: movie.title,
+                audio_locales: audio_locales(vec![], &movie.slug_title),
+                subtitle_locales: vec![],
+                description: movie.description,
+                score,
+                rank,
+                popularity_score,
+                release_date: None,
+            })
+        }
+    })
+}
+
+pub fn sort_json(mut object: serde_json::Map<String, Value>) -> serde_json::Map<String, Value> {
+    let mut sorted = serde_json::Map::with_capacity(object.len());
+
+    for arg in std::env::args() {
+        for (key, value) in object.clone() {
+            // map the serialized key back onto the (kebab-case) flag which selects it
+            let flag = match key.as_str() {
+                "popularity_score" => "popularity".to_string(),
+                other => other.replace('_', "-"),
+            };
+            if arg == format!("--{}", flag) {
+                object.remove(&key);
+                sorted.insert(key, value);
+                break;
+            }
+        }
+    }
+
+    sorted
+}
+
+fn json_value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Array(values) => values
+            .iter()
+            .map(json_value_to_string)
+            .collect::<Vec<String>>()
+            .join(","),
+        other => other.to_string(),
+    }
+}
+
+pub fn print_outputs(
+    outputs: Vec<FormattedOutput>,
+    output_format: &OutputFormat,
+) -> anyhow::Result<()> {
+    let as_maps = outputs
+        .into_iter()
+        .map(|output| {
+            let as_json = serde_json::to_value(&output)?;
+            Ok(sort_json(as_json.as_object().expect("json object").clone()))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    match output_format {
+        OutputFormat::Csv => {
+            for as_map in &as_maps {
+                println!(
+                    "{}",
+                    as_map
+                        .values()
+                        .into_iter()
+                        .map(json_value_to_string)
+                        .collect::<Vec<String>>()
+                        .join(";")
+                )
+            }
+        }
+        OutputFormat::QuotedCsv => {
+            for as_map in &as_maps {
+                let mut csv = vec![];
+                for value in as_map.values().into_iter() {
+                    let mut buf = String::new();
+                    buf.push('"');
+
+                    // generate the csv
+                    let value_as_string = json_value_to_string(value);
+                    for char in value_as_string.chars() {
+                        if char == '"' {
+                            buf.push('"');
+                        } else if char == '\r' || char == '\n' {
+                            continue;
+                        }
+                        buf.push(char)
+                    }
+                    buf.push('"');
+
+                    csv.push(buf)
+                }
+
+                println!("{}", csv.join(";"))
+            }
+        }
+        OutputFormat::Json => {
+            for as_map in &as_maps {
+                println!("{}", serde_json::to_string(as_map)?)
+            }
+        }
+        #[cfg(feature = "report-yaml")]
+        OutputFormat::Yaml => {
+            for as_map in &as_maps {
+                print!("{}", serde_yaml::to_string(as_map)?)
+            }
+        }
+        OutputFormat::Table => print_table(&as_maps),
+    }
+
+    Ok(())
+}
+
+/// Column set for a table, as the union of keys across all rows in the order they're first seen.
+/// `skip_serializing_none` drops per-row keys whose value is `None`, so a column missing from the
+/// first row (e.g. a score-less hit mixed with scored ones) is still a column here.
+fn table_columns(as_maps: &[serde_json::Map<String, Value>]) -> Vec<&String> {
+    let mut columns: Vec<&String> = vec![];
+    for as_map in as_maps {
+        for key in as_map.keys() {
+            if !columns.contains(&key) {
+                columns.push(key);
+            }
+        }
+    }
+    columns
+}
+
+fn print_table(as_maps: &[serde_json::Map<String, Value>]) {
+    if as_maps.is_empty() {
+        return;
+    }
+    let columns = table_columns(as_maps);
+
+    let rows: Vec<Vec<String>> = as_maps
+        .iter()
+        .map(|as_map| {
+            columns
+                .iter()
+                .map(|column| {
+                    as_map
+                        .get(*column)
+                        .map(json_value_to_string)
+                        .unwrap_or_default()
+                })
+                .collect()
+        })
+        .collect();
+
+    let mut widths: Vec<usize> = columns.iter().map(|column| column.len()).collect();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    let pad_row = |cells: Vec<String>| -> String {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:width$}", cell, width = width))
+            .collect::<Vec<String>>()
+            .join(" | ")
+            .trim_end()
+            .to_string()
+    };
+
+    println!(
+        "{}",
+        pad_row(columns.into_iter().cloned().collect::<Vec<String>>())
+    );
+    for row in rows {
+        println!("{}", pad_row(row));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output_with_score(score: f64) -> Output {
+        Output {
+            score: Some(score),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn sort_outputs_by_score_orders_highest_first() {
+        let mut outputs = vec![
+            output_with_score(0.2),
+            output_with_score(0.9),
+            output_with_score(0.5),
+        ];
+        sort_outputs(&mut outputs, &Some(SortBy::Score));
+        let scores: Vec<f64> = outputs.iter().map(|o| o.score.unwrap()).collect();
+        assert_eq!(scores, vec![0.9, 0.5, 0.2]);
+    }
+
+    #[test]
+    fn sort_outputs_by_rank_orders_lowest_first_and_unranked_last() {
+        let mut outputs = vec![
+            Output {
+                rank: Some(3),
+                ..Default::default()
+            },
+            Output {
+                rank: None,
+                ..Default::default()
+            },
+            Output {
+                rank: Some(1),
+                ..Default::default()
+            },
+        ];
+        sort_outputs(&mut outputs, &Some(SortBy::Rank));
+        let ranks: Vec<Option<u32>> = outputs.iter().map(|o| o.rank).collect();
+        assert_eq!(ranks, vec![Some(1), Some(3), None]);
+    }
+
+    #[test]
+    fn sort_outputs_without_sort_by_keeps_original_order() {
+        let mut outputs = vec![output_with_score(0.2), output_with_score(0.9)];
+        sort_outputs(&mut outputs, &None);
+        let scores: Vec<f64> = outputs.iter().map(|o| o.score.unwrap()).collect();
+        assert_eq!(scores, vec![0.2, 0.9]);
+    }
+
+    fn column_names(as_maps: &[serde_json::Map<String, Value>]) -> Vec<&str> {
+        table_columns(as_maps).into_iter().map(String::as_str).collect()
+    }
+
+    #[test]
+    fn table_columns_is_union_across_rows_not_just_the_first() {
+        let as_maps = vec![
+            serde_json::json!({"title": "a"}).as_object().unwrap().clone(),
+            serde_json::json!({"title": "b", "score": 0.9}).as_object().unwrap().clone(),
+        ];
+        assert_eq!(column_names(&as_maps), vec!["title", "score"]);
+    }
+
+    #[test]
+    fn table_columns_preserves_first_seen_order() {
+        let as_maps = vec![
+            serde_json::json!({"b": 1, "a": 2}).as_object().unwrap().clone(),
+            serde_json::json!({"c": 3}).as_object().unwrap().clone(),
+        ];
+        assert_eq!(column_names(&as_maps), vec!["b", "a", "c"]);
+    }
+
+    #[test]
+    fn locale_from_slug_recognizes_known_dub_suffixes() {
+        assert_eq!(
+            locale_from_slug("attack-on-titan-german-dub"),
+            Some("de-DE".to_string())
+        );
+        assert_eq!(
+            locale_from_slug("attack-on-titan-castilian-dub"),
+            Some("es-ES".to_string())
+        );
+        assert_eq!(
+            locale_from_slug("attack-on-titan-english-dub"),
+            Some("en-US".to_string())
+        );
+    }
+
+    #[test]
+    fn locale_from_slug_returns_none_for_unrecognized_or_undubbed_slugs() {
+        assert_eq!(locale_from_slug("attack-on-titan-french-dub"), None);
+        assert_eq!(locale_from_slug("attack-on-titan"), None);
+    }
+
+    #[test]
+    fn filter_by_locale_keeps_outputs_matching_audio_or_subtitles() {
+        let outputs = vec![
+            Output {
+                audio_locales: vec!["de-DE".to_string()],
+                ..Default::default()
+            },
+            Output {
+                subtitle_locales: vec!["de-DE".to_string()],
+                ..Default::default()
+            },
+            Output {
+                audio_locales: vec!["en-US".to_string()],
+                ..Default::default()
+            },
+        ];
+        let filtered = filter_by_locale(outputs, &Some("de-DE".to_string()));
+        assert_eq!(filtered.len(), 2);
+    }
+
+    #[test]
+    fn filter_by_locale_without_locale_returns_all_outputs() {
+        let outputs = vec![Output::default(), Output::default()];
+        let filtered = filter_by_locale(outputs, &None);
+        assert_eq!(filtered.len(), 2);
+    }
+}
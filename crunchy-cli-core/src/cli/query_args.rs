@@ -0,0 +1,160 @@
+use crate::cli::output::{
+    convert_to_formatted_outputs, expand_series, filter_by_locale, media_collection_to_output,
+    print_outputs, sort_outputs, Expand, OutputFields, OutputFormat, SortBy,
+};
+use crate::utils::context::Context;
+use crate::utils::parse::parse_url;
+use anyhow::bail;
+use crunchyroll_rs::search::{QueryOptions, QueryType};
+use crunchyroll_rs::MediaCollection;
+
+#[derive(Clone, Debug)]
+pub enum ResultType {
+    Series,
+    Episode,
+    Movie,
+}
+
+impl ResultType {
+    fn parse(s: &str) -> Result<ResultType, String> {
+        Ok(match s.to_lowercase().as_str() {
+            "series" => ResultType::Series,
+            "episode" | "episodes" => ResultType::Episode,
+            "movie" | "movies" => ResultType::Movie,
+            _ => return Err(format!("'{}' is not a valid result type", s)),
+        })
+    }
+}
+
+/// Arguments shared by `query` and `search`: both resolve `input` to a single result or run a
+/// keyword lookup the exact same way, and only differ in the `about` text shown for the command
+/// itself. Keeping this in one place means a new flag only has to be added once instead of twice
+/// in lockstep.
+#[derive(Debug, clap::Args)]
+pub struct QueryArgs {
+    #[arg(help = "Number of results to fetch")]
+    #[arg(short = 'n', long, default_value_t = 10)]
+    limit: u32,
+    #[arg(help = "Type of results to return. \
+    Available options are: 'series', 'episodes', 'movies'. \
+    None means mixed")]
+    #[arg(long)]
+    #[arg(value_parser = ResultType::parse)]
+    result_type: Option<ResultType>,
+    #[arg(help = "Reorder the results best-matches-first. \
+    Available options are: 'score' and 'rank'")]
+    #[arg(long)]
+    #[arg(value_parser = SortBy::parse)]
+    sort_by: Option<SortBy>,
+    #[arg(help = "Expand a series match into its children instead of a single row. \
+    Available options are: 'seasons' and 'episodes'")]
+    #[arg(long)]
+    #[arg(value_parser = Expand::parse)]
+    expand: Option<Expand>,
+    #[arg(help = "Only return results available in the given locale, e.g. 'de-DE'")]
+    #[arg(long)]
+    only_locale: Option<String>,
+    #[arg(help = "Resolve the input to a series/movie and return its recommendations instead \
+    of matching by keyword")]
+    #[arg(long, default_value_t = false)]
+    similar: bool,
+
+    #[arg(long, default_value_t = false)]
+    id: bool,
+    #[arg(long, default_value_t = false)]
+    url: bool,
+    #[arg(long = "type", default_value_t = false)]
+    type_: bool,
+    #[arg(long, default_value_t = false)]
+    title: bool,
+    #[arg(long, default_value_t = false)]
+    description: bool,
+    #[arg(long, default_value_t = false)]
+    score: bool,
+    #[arg(long, default_value_t = false)]
+    rank: bool,
+    #[arg(long, default_value_t = false)]
+    popularity: bool,
+    #[arg(long, default_value_t = false)]
+    audio_locales: bool,
+    #[arg(long, default_value_t = false)]
+    subtitle_locales: bool,
+
+    #[arg(help = "Format in which the output should be displayed. \
+    Available options are: 'csv', 'json' and 'table'")]
+    #[arg(long_help = "Format in which the output should be displayed. \
+    Available options are: 'csv', 'quoted-csv', 'json', 'table' and (when built with the \
+    'report-yaml' feature) 'yaml'. Note that 'quoted-csv' will remove all newlines to keep \
+    the output parsable")]
+    #[arg(long, default_value = "csv")]
+    #[arg(value_parser = OutputFormat::parse)]
+    output_format: OutputFormat,
+
+    input: String,
+}
+
+impl QueryArgs {
+    fn output_fields(&self) -> OutputFields {
+        OutputFields {
+            id: self.id,
+            url: self.url,
+            type_: self.type_,
+            title: self.title,
+            description: self.description,
+            score: self.score,
+            rank: self.rank,
+            popularity_score: self.popularity,
+            release_date: false,
+            audio_locales: self.audio_locales,
+            subtitle_locales: self.subtitle_locales,
+        }
+    }
+
+    pub async fn execute(self, ctx: Context) -> anyhow::Result<()> {
+        let results = if self.similar {
+            let (media_collection, _) = parse_url(&ctx.crunchy, self.input.clone(), true).await?;
+            let items = match media_collection {
+                MediaCollection::Series(series) => series.similar().await?.items,
+                MediaCollection::MovieListing(movie_listing) => {
+                    movie_listing.similar().await?.items
+                }
+                _ => bail!("'{}' does not point to a series or movie", self.input),
+            };
+            items.into_iter().take(self.limit as usize).collect()
+        } else if crunchyroll_rs::parse_url(self.input.clone()).is_some() {
+            vec![parse_url(&ctx.crunchy, self.input.clone(), true).await?.0]
+        } else {
+            let mut query_options = QueryOptions::default().limit(self.limit);
+            if let Some(result_type) = &self.result_type {
+                query_options = match result_type {
+                    ResultType::Series => query_options.result_type(QueryType::Series),
+                    ResultType::Episode => query_options.result_type(QueryType::Episode),
+                    ResultType::Movie => query_options.result_type(QueryType::MovieListing),
+                }
+            }
+            let query = ctx.crunchy.query(&self.input, query_options).await?;
+            query.top_results.unwrap().items
+        };
+
+        let mut outputs = vec![];
+
+        for result in results {
+            match result {
+                MediaCollection::Series(series) if self.expand.is_some() => {
+                    outputs.extend(expand_series(series, self.expand.as_ref().unwrap()).await?)
+                }
+                other => {
+                    if let Some(output) = media_collection_to_output(other).await? {
+                        outputs.push(output)
+                    }
+                }
+            }
+        }
+
+        sort_outputs(&mut outputs, &self.sort_by);
+        outputs = filter_by_locale(outputs, &self.only_locale);
+
+        let formatted_outputs = convert_to_formatted_outputs(self.output_fields(), outputs);
+        print_outputs(formatted_outputs, &self.output_format)
+    }
+}